@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::Engine;
+use tokio::sync::Mutex;
+
+/// Shared cache of bearer tokens obtained via the `WWW-Authenticate: Bearer`
+/// challenge flow, keyed by `"<registry>|<scope>"` so a token for one
+/// repository scope isn't reused for another.
+pub type TokenCache = Arc<Mutex<HashMap<String, String>>>;
+
+pub fn new_token_cache() -> TokenCache {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+/// Removes the cached bearer token for `registry`/`scope`, so the next
+/// `resolve_header` call fetches a fresh one instead of reusing a token the
+/// registry has since rejected (e.g. after it expired).
+pub async fn evict(cache: &TokenCache, registry: &str, scope: &str) {
+    cache.lock().await.remove(&format!("{}|{}", registry, scope));
+}
+
+#[derive(Debug, Clone)]
+pub struct BearerChallenge {
+    pub realm: String,
+    pub service: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// Parses a `WWW-Authenticate: Bearer realm="...",service="...",scope="..."`
+/// header value into its component parts. Returns `None` if the header isn't
+/// a Bearer challenge.
+pub fn parse_bearer_challenge(header_value: &str) -> Option<BearerChallenge> {
+    let rest = header_value.strip_prefix("Bearer ")?;
+
+    let mut realm = None;
+    let mut service = None;
+    let mut scope = None;
+
+    for pair in split_challenge_params(rest) {
+        let mut parts = pair.splitn(2, '=');
+        let key = parts.next()?.trim();
+        let value = parts.next()?.trim().trim_matches('"').to_string();
+        match key {
+            "realm" => realm = Some(value),
+            "service" => service = Some(value),
+            "scope" => scope = Some(value),
+            _ => {}
+        }
+    }
+
+    Some(BearerChallenge {
+        realm: realm?,
+        service,
+        scope,
+    })
+}
+
+/// Splits `key="value",key="value"` on top-level commas, ignoring commas that
+/// appear inside quoted values (the `scope` value is often itself a
+/// comma-separated list, e.g. `repository:a:pull,push`).
+fn split_challenge_params(input: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            ',' if !in_quotes => {
+                parts.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    parts
+}
+
+/// Exchanges a bearer challenge for a token by calling the challenge's
+/// `realm` with `service`/`scope` query params, optionally authenticating
+/// with Basic credentials.
+pub async fn fetch_token(
+    http: &reqwest::Client,
+    challenge: &BearerChallenge,
+    basic: Option<(&str, &str)>,
+) -> Result<String, String> {
+    let mut req = http.get(&challenge.realm);
+    let mut query: Vec<(&str, &str)> = Vec::new();
+    if let Some(service) = &challenge.service {
+        query.push(("service", service));
+    }
+    if let Some(scope) = &challenge.scope {
+        query.push(("scope", scope));
+    }
+    req = req.query(&query);
+
+    if let Some((user, pass)) = basic {
+        req = req.basic_auth(user, Some(pass));
+    }
+
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| format!("Failed to request auth token: {}", e))?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Auth token request failed with status {}", resp.status()));
+    }
+
+    let body: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse auth token response: {}", e))?;
+
+    body.get("token")
+        .or_else(|| body.get("access_token"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Auth token response did not contain a token".to_string())
+}
+
+/// Resolves the `Authorization` header to use for raw HTTP requests against
+/// `registry`/`scope`: an explicit `token` wins outright, then a cached
+/// bearer token, then a fresh bearer token obtained by pinging `GET /v2/` and
+/// following the `WWW-Authenticate` challenge (using `basic` credentials, if
+/// any, to obtain it), falling back to plain Basic auth, and finally no auth
+/// at all.
+pub async fn resolve_header(
+    http: &reqwest::Client,
+    ping_url: &str,
+    registry: &str,
+    scope: &str,
+    basic: Option<(String, String)>,
+    token: Option<String>,
+    cache: &TokenCache,
+) -> Option<String> {
+    if let Some(token) = token {
+        return Some(format!("Bearer {}", token));
+    }
+
+    let key = format!("{}|{}", registry, scope);
+    if let Some(cached) = cache.lock().await.get(&key) {
+        return Some(format!("Bearer {}", cached));
+    }
+
+    let basic_header = basic
+        .as_ref()
+        .map(|(u, p)| format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", u, p))));
+
+    let ping = http.get(ping_url).send().await.ok()?;
+    if ping.status() == reqwest::StatusCode::UNAUTHORIZED {
+        if let Some(challenge) = ping
+            .headers()
+            .get(reqwest::header::WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_bearer_challenge)
+        {
+            let basic_ref = basic.as_ref().map(|(u, p)| (u.as_str(), p.as_str()));
+            if let Ok(token) = fetch_token(http, &challenge, basic_ref).await {
+                cache.lock().await.insert(key, token.clone());
+                return Some(format!("Bearer {}", token));
+            }
+        }
+    }
+
+    basic_header
+}
+
+/// Reads Basic credentials for `registry` out of `~/.docker/config.json`,
+/// decoding the base64 `auths.<registry>.auth` entry into username/password.
+pub fn docker_config_credentials(registry: &str) -> Option<(String, String)> {
+    let home = std::env::var("HOME").ok()?;
+    let path = std::path::Path::new(&home).join(".docker").join("config.json");
+    let contents = std::fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&contents).ok()?;
+    let auth_b64 = json
+        .get("auths")?
+        .get(registry)?
+        .get("auth")?
+        .as_str()?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(auth_b64).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (user, pass) = decoded.split_once(':')?;
+    Some((user.to_string(), pass.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_realm_service_and_scope() {
+        let challenge = parse_bearer_challenge(
+            r#"Bearer realm="https://auth.example.com/token",service="registry.example.com",scope="repository:foo/bar:pull""#,
+        ).unwrap();
+        assert_eq!(challenge.realm, "https://auth.example.com/token");
+        assert_eq!(challenge.service.as_deref(), Some("registry.example.com"));
+        assert_eq!(challenge.scope.as_deref(), Some("repository:foo/bar:pull"));
+    }
+
+    #[test]
+    fn scope_with_embedded_commas_is_kept_whole() {
+        let challenge = parse_bearer_challenge(
+            r#"Bearer realm="https://auth.example.com/token",service="registry.example.com",scope="repository:foo/bar:pull,push""#,
+        ).unwrap();
+        assert_eq!(challenge.scope.as_deref(), Some("repository:foo/bar:pull,push"));
+    }
+
+    #[test]
+    fn service_and_scope_are_optional() {
+        let challenge = parse_bearer_challenge(r#"Bearer realm="https://auth.example.com/token""#).unwrap();
+        assert_eq!(challenge.realm, "https://auth.example.com/token");
+        assert_eq!(challenge.service, None);
+        assert_eq!(challenge.scope, None);
+    }
+
+    #[test]
+    fn non_bearer_challenge_returns_none() {
+        assert!(parse_bearer_challenge(r#"Basic realm="registry""#).is_none());
+    }
+
+    #[test]
+    fn missing_realm_returns_none() {
+        assert!(parse_bearer_challenge(r#"Bearer service="registry.example.com""#).is_none());
+    }
+}
@@ -0,0 +1,36 @@
+use sha2::{Digest, Sha256};
+
+/// Computes the `sha256:<hex>` digest of `data`, in the form OCI registries
+/// use to address blobs and manifests.
+pub fn sha256_hex(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("sha256:{}", hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_known_sha256_digest() {
+        // echo -n "hello world" | sha256sum
+        assert_eq!(
+            sha256_hex(b"hello world"),
+            "sha256:b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[test]
+    fn empty_input_matches_known_digest() {
+        assert_eq!(
+            sha256_hex(b""),
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn different_inputs_produce_different_digests() {
+        assert_ne!(sha256_hex(b"a"), sha256_hex(b"b"));
+    }
+}
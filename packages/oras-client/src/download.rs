@@ -0,0 +1,95 @@
+use napi::bindgen_prelude::*;
+use oci_client::client::ClientProtocol;
+use oci_client::Reference;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use futures::StreamExt;
+
+use crate::upload::{blob_base_url, ProgressCallback, ProgressSink};
+
+/// Streams the blob `digest` from `reference` directly to `dest_path`,
+/// chunk-by-chunk, so large layers don't have to be buffered in memory.
+/// When `verify` is set, the running sha256 of the streamed bytes is checked
+/// against `digest` once the download completes.
+pub(crate) async fn pull_blob_to_file(
+    http: &reqwest::Client,
+    protocol: &ClientProtocol,
+    reference: &Reference,
+    digest: &str,
+    dest_path: &str,
+    auth_header: Option<&str>,
+    progress: Option<ProgressCallback>,
+    verify: bool,
+) -> Result<()> {
+    let result = stream_blob_to_file(http, protocol, reference, digest, dest_path, auth_header, progress, verify).await;
+    if result.is_err() {
+        // Don't leave a half-written or digest-mismatched file at dest_path for
+        // a caller to mistake for a complete, trustworthy blob.
+        let _ = tokio::fs::remove_file(dest_path).await;
+    }
+    result
+}
+
+async fn stream_blob_to_file(
+    http: &reqwest::Client,
+    protocol: &ClientProtocol,
+    reference: &Reference,
+    digest: &str,
+    dest_path: &str,
+    auth_header: Option<&str>,
+    progress: Option<ProgressCallback>,
+    verify: bool,
+) -> Result<()> {
+    let url = format!("{}/blobs/{}", blob_base_url(protocol, reference), digest);
+
+    let mut req = http.get(&url);
+    if let Some(auth) = auth_header {
+        req = req.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to pull blob: {}", e)))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(crate::error::from_response(status, &body, "Failed to pull blob"));
+    }
+
+    let mut file = tokio::fs::File::create(dest_path)
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to create destination file: {}", e)))?;
+
+    let mut stream = resp.bytes_stream();
+    let mut hasher = Sha256::new();
+    let mut written = 0usize;
+    let progress = ProgressSink::standalone(progress);
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| Error::from_reason(format!("Failed to read blob stream: {}", e)))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| Error::from_reason(format!("Failed to write blob to file: {}", e)))?;
+        if verify {
+            hasher.update(&chunk);
+        }
+        written += chunk.len();
+        progress.report(written);
+    }
+    file.flush()
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to flush destination file: {}", e)))?;
+
+    if verify {
+        let actual = format!("sha256:{}", hex::encode(hasher.finalize()));
+        if actual != digest {
+            return Err(Error::from_reason(format!(
+                "Blob digest mismatch: expected {}, got {}",
+                digest, actual
+            )));
+        }
+    }
+
+    Ok(())
+}
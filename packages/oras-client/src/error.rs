@@ -0,0 +1,168 @@
+use napi::bindgen_prelude::Error;
+use serde::{Deserialize, Serialize};
+
+/// Structured error surface thrown to JS for registry/HTTP failures.
+/// `httpStatus`/`code` let callers branch on error type (retry on
+/// TOOMANYREQUESTS, treat MANIFEST_UNKNOWN as not-found) instead of
+/// string-matching the message. Thrown as the napi `Error`'s reason, JSON
+/// encoded; JS callers should `JSON.parse(err.message)`.
+#[derive(Serialize)]
+struct OciErrorInfo {
+    #[serde(rename = "httpStatus")]
+    http_status: Option<u16>,
+    code: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct OciErrorEnvelope {
+    errors: Vec<OciErrorEntry>,
+}
+
+#[derive(Deserialize)]
+struct OciErrorEntry {
+    code: String,
+    message: String,
+}
+
+/// Builds a structured error from a raw HTTP response, parsing the OCI error
+/// envelope (`{"errors":[{"code":"MANIFEST_UNKNOWN","message":...}]}`) out of
+/// `body` when present (e.g. `BLOB_UNKNOWN`, `NAME_UNKNOWN`, `UNAUTHORIZED`,
+/// `DENIED`, `TOOMANYREQUESTS`), and always carrying the HTTP status.
+pub fn from_response(status: reqwest::StatusCode, body: &str, context: &str) -> Error {
+    let (code, message) = match serde_json::from_str::<OciErrorEnvelope>(body) {
+        Ok(envelope) if !envelope.errors.is_empty() => {
+            (envelope.errors[0].code.clone(), envelope.errors[0].message.clone())
+        }
+        _ => ("UNKNOWN".to_string(), format!("{}: {} {}", context, status, body)),
+    };
+
+    to_error(OciErrorInfo {
+        http_status: Some(status.as_u16()),
+        code,
+        message,
+    })
+}
+
+/// Wraps a generic error (I/O, parsing, a plain `&str`/`String` detail, ...)
+/// in the same structured shape as `from_response`. There's no HTTP response
+/// behind these, so `httpStatus` is always `None` and `code` is `UNKNOWN`.
+pub fn wrap<E: std::fmt::Display>(context: &str, err: E) -> Error {
+    to_error(OciErrorInfo {
+        http_status: None,
+        code: "UNKNOWN".to_string(),
+        message: format!("{}: {}", context, err),
+    })
+}
+
+/// Wraps an error returned by an `oci-client` `Client` method in the same
+/// structured shape as `from_response`. The crate doesn't give typed access
+/// to the registry's HTTP status or error envelope, but it does render both
+/// into the error's `Display` text for registry-rejected requests (e.g.
+/// `"...404 Not Found...{\"errors\":[{\"code\":\"MANIFEST_UNKNOWN\",...}]}"`)
+/// — best-effort recover them from there instead of always falling back to
+/// `UNKNOWN`/`None`, so the oci-client-backed methods (`pull_manifest`,
+/// `push_manifest`, `list_tags`, ...) get the same structured `code`s as the
+/// raw-HTTP chunked-upload/streaming-pull paths.
+pub fn wrap_oci<E: std::fmt::Display>(context: &str, err: E) -> Error {
+    let rendered = err.to_string();
+    to_error(OciErrorInfo {
+        http_status: extract_http_status(&rendered),
+        code: extract_envelope_code(&rendered).unwrap_or_else(|| "UNKNOWN".to_string()),
+        message: format!("{}: {}", context, rendered),
+    })
+}
+
+/// Finds an embedded `{"errors":[{"code":"...","message":"..."}]}` OCI error
+/// envelope inside `text` and returns its first entry's `code`.
+fn extract_envelope_code(text: &str) -> Option<String> {
+    let start = text.find("{\"errors\"")?;
+    serde_json::from_str::<OciErrorEnvelope>(&text[start..])
+        .ok()
+        .and_then(|envelope| envelope.errors.into_iter().next())
+        .map(|entry| entry.code)
+}
+
+/// Finds an HTTP status code inside `text` by looking for a 3-digit token
+/// immediately followed by its own canonical reason phrase (e.g. the `404` in
+/// `"...404 Not Found..."`, matching how `reqwest::StatusCode`'s `Display` —
+/// and this module's own `from_response` — render a status). Anchoring on the
+/// reason phrase (rather than scanning for any digit run in 100-599) avoids
+/// false positives from digit runs embedded in unrelated text, e.g. a sha256
+/// digest fragment like the `149` in `"...fc1c149afbf..."`.
+fn extract_http_status(text: &str) -> Option<u16> {
+    let bytes = text.as_bytes();
+    for start in 0..bytes.len() {
+        if start + 3 > bytes.len() {
+            break;
+        }
+        let before_is_digit = start > 0 && bytes[start - 1].is_ascii_digit();
+        let after_is_digit = bytes.get(start + 3).is_some_and(u8::is_ascii_digit);
+        if before_is_digit || after_is_digit {
+            continue;
+        }
+        if !bytes[start..start + 3].iter().all(u8::is_ascii_digit) {
+            continue;
+        }
+
+        let Ok(code) = text[start..start + 3].parse::<u16>() else {
+            continue;
+        };
+        let Ok(status) = reqwest::StatusCode::from_u16(code) else {
+            continue;
+        };
+        let Some(reason) = status.canonical_reason() else {
+            continue;
+        };
+        let rest = text[start + 3..].trim_start();
+        if rest.to_lowercase().starts_with(&reason.to_lowercase()) {
+            return Some(code);
+        }
+    }
+    None
+}
+
+fn to_error(info: OciErrorInfo) -> Error {
+    let message = info.message.clone();
+    Error::from_reason(serde_json::to_string(&info).unwrap_or(message))
+}
+
+/// Recovers the `httpStatus` carried by an `Error` built via `from_response`,
+/// so callers that need to react to a specific status (e.g. retrying once
+/// after evicting a stale cached bearer token on a 401) don't have to
+/// re-request or guess it from the message text.
+pub fn status_of(err: &Error) -> Option<u16> {
+    serde_json::from_str::<serde_json::Value>(&err.reason)
+        .ok()?
+        .get("httpStatus")?
+        .as_u64()
+        .map(|s| s as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_status_from_reqwest_style_rendering() {
+        assert_eq!(extract_http_status("Failed to push manifest: 404 Not Found"), Some(404));
+        assert_eq!(extract_http_status("request failed with 401 Unauthorized"), Some(401));
+    }
+
+    #[test]
+    fn ignores_digit_runs_embedded_in_a_digest() {
+        let text = "manifest blob sha256:deadbeeffc1c149afbf1234567890 not found";
+        assert_eq!(extract_http_status(text), None);
+    }
+
+    #[test]
+    fn ignores_digit_runs_not_followed_by_a_reason_phrase() {
+        assert_eq!(extract_http_status("retry after 500 milliseconds"), None);
+    }
+
+    #[test]
+    fn finds_status_after_other_digits_in_the_text() {
+        let text = "digest sha256:abc123 rejected: 403 Forbidden";
+        assert_eq!(extract_http_status(text), Some(403));
+    }
+}
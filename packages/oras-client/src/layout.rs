@@ -0,0 +1,287 @@
+use std::path::Path;
+
+use napi::bindgen_prelude::*;
+use oci_client::manifest::{OciDescriptor, OciImageIndex, OciImageManifest};
+use oci_client::secrets::RegistryAuth;
+use oci_client::{Client, Reference};
+use std::str::FromStr;
+
+use crate::auth;
+use crate::digest::sha256_hex;
+use crate::error;
+
+const OCI_LAYOUT_MARKER: &str = r#"{"imageLayoutVersion":"1.0.0"}"#;
+const MEDIA_TYPE_IMAGE_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+const MEDIA_TYPE_IMAGE_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+
+fn digest_hex(digest: &str) -> &str {
+    digest.strip_prefix("sha256:").unwrap_or(digest)
+}
+
+fn blob_path(dir: &Path, digest: &str) -> std::path::PathBuf {
+    dir.join("blobs").join("sha256").join(digest_hex(digest))
+}
+
+async fn write_blob(dir: &Path, digest: &str, data: &[u8]) -> Result<()> {
+    let path = blob_path(dir, digest);
+    tokio::fs::write(&path, data)
+        .await
+        .map_err(|e| error::wrap(&format!("Failed to write blob {}", digest), e))
+}
+
+fn child_reference(reference: &Reference, digest: &str) -> Result<Reference> {
+    Reference::from_str(&format!(
+        "{}/{}@{}",
+        reference.resolve_registry(),
+        reference.repository(),
+        digest
+    ))
+    .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Pulls `image_ref` (manifest or full index) and writes the standard OCI
+/// Image Layout to `dir`: the `oci-layout` marker, `index.json`, and every
+/// referenced config/layer blob (recursing through index entries) under
+/// `blobs/sha256/`.
+pub async fn export_to_layout(
+    client: &Client,
+    reference: &Reference,
+    auth: &RegistryAuth,
+    dir: &Path,
+) -> Result<()> {
+    tokio::fs::create_dir_all(dir.join("blobs").join("sha256"))
+        .await
+        .map_err(|e| error::wrap("Failed to create OCI layout directory", e))?;
+    tokio::fs::write(dir.join("oci-layout"), OCI_LAYOUT_MARKER)
+        .await
+        .map_err(|e| error::wrap("Failed to write oci-layout marker", e))?;
+
+    let (manifest_bytes, digest) = client
+        .pull_manifest_raw(reference, auth, &[])
+        .await
+        .map_err(|e| error::wrap_oci("Failed to pull manifest", e))?;
+
+    let media_type = export_tree(client, reference, auth, dir, &manifest_bytes, &digest).await?;
+
+    let root = OciDescriptor {
+        media_type,
+        digest,
+        size: manifest_bytes.len() as i64,
+        urls: None,
+        annotations: None,
+        platform: None,
+    };
+    write_index_json(dir, vec![root]).await
+}
+
+/// Recursively writes `bytes` (a manifest or index, already known to be
+/// stored at `digest`) and everything it references to `dir`, returning the
+/// media type of `bytes` itself.
+fn export_tree<'a>(
+    client: &'a Client,
+    reference: &'a Reference,
+    auth: &'a RegistryAuth,
+    dir: &'a Path,
+    bytes: &'a [u8],
+    digest: &'a str,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String>> + Send + 'a>> {
+    Box::pin(async move {
+        write_blob(dir, digest, bytes).await?;
+
+        if let Ok(index) = serde_json::from_slice::<OciImageIndex>(bytes) {
+            if !index.manifests.is_empty() {
+                for entry in &index.manifests {
+                    let child_ref = child_reference(reference, &entry.digest)?;
+                    let (child_bytes, child_digest) = client
+                        .pull_manifest_raw(&child_ref, auth, &[])
+                        .await
+                        .map_err(|e| error::wrap_oci("Failed to pull referenced manifest", e))?;
+                    export_tree(client, reference, auth, dir, &child_bytes, &child_digest).await?;
+                }
+                return Ok(MEDIA_TYPE_IMAGE_INDEX.to_string());
+            }
+        }
+
+        let manifest: OciImageManifest = serde_json::from_slice(bytes)
+            .map_err(|e| Error::from_reason(format!("Failed to parse manifest: {}", e)))?;
+
+        let config_bytes = pull_blob(client, reference, auth, &manifest.config.digest).await?;
+        write_blob(dir, &manifest.config.digest, &config_bytes).await?;
+
+        for layer in &manifest.layers {
+            let layer_bytes = pull_blob(client, reference, auth, &layer.digest).await?;
+            write_blob(dir, &layer.digest, &layer_bytes).await?;
+        }
+
+        Ok(MEDIA_TYPE_IMAGE_MANIFEST.to_string())
+    })
+}
+
+async fn pull_blob(client: &Client, reference: &Reference, auth: &RegistryAuth, digest: &str) -> Result<Vec<u8>> {
+    client.store_auth_if_needed(reference.resolve_registry(), auth).await;
+    let mut content = Vec::new();
+    client
+        .pull_blob(reference, digest, &mut content)
+        .await
+        .map_err(|e| error::wrap_oci(&format!("Failed to pull blob {}", digest), e))?;
+
+    let computed = sha256_hex(&content);
+    if computed != digest {
+        return Err(Error::from_reason(format!(
+            "Blob digest mismatch while exporting: expected {}, got {}",
+            digest, computed
+        )));
+    }
+
+    Ok(content)
+}
+
+async fn write_index_json(dir: &Path, manifests: Vec<OciDescriptor>) -> Result<()> {
+    let index = OciImageIndex {
+        schema_version: 2,
+        media_type: Some(MEDIA_TYPE_IMAGE_INDEX.to_string()),
+        manifests,
+        annotations: None,
+    };
+    let bytes = serde_json::to_vec_pretty(&index)
+        .map_err(|e| Error::from_reason(format!("Failed to serialize index.json: {}", e)))?;
+    tokio::fs::write(dir.join("index.json"), bytes)
+        .await
+        .map_err(|e| error::wrap("Failed to write index.json", e))
+}
+
+/// Evicts the cached bearer token for `reference` and resolves a fresh
+/// `Authorization` header, mirroring `OrasClient::refresh_raw_auth_header`
+/// for the raw-HTTP blob uploads in `push_tree` (this free-function path has
+/// no `AuthOptions` to re-derive a `token`/docker-config override from, only
+/// the `RegistryAuth` already resolved by the caller, so `Basic` is the only
+/// credential that can be retried here).
+async fn refresh_auth_header(
+    http: &reqwest::Client,
+    protocol: &oci_client::client::ClientProtocol,
+    reference: &Reference,
+    auth: &RegistryAuth,
+    token_cache: &auth::TokenCache,
+) -> Option<String> {
+    let registry = reference.resolve_registry();
+    let scope = format!("repository:{}:pull,push", reference.repository());
+    auth::evict(token_cache, registry, &scope).await;
+
+    let basic = match auth {
+        RegistryAuth::Basic(user, pass) => Some((user.clone(), pass.clone())),
+        _ => None,
+    };
+    let scheme = match protocol {
+        oci_client::client::ClientProtocol::Https => "https",
+        oci_client::client::ClientProtocol::Http => "http",
+        oci_client::client::ClientProtocol::HttpsExcept(_) => "https",
+    };
+    let ping_url = format!("{}://{}/v2/", scheme, registry);
+
+    auth::resolve_header(http, &ping_url, registry, &scope, basic, None, token_cache).await
+}
+
+/// Reads an OCI Image Layout from `dir` and pushes its top-level manifest (or
+/// index, with every referenced manifest and blob) to `image_ref`.
+pub async fn push_from_layout(
+    client: &Client,
+    http: &reqwest::Client,
+    protocol: &oci_client::client::ClientProtocol,
+    reference: &Reference,
+    auth: &RegistryAuth,
+    auth_header: Option<&str>,
+    token_cache: &auth::TokenCache,
+    dir: &Path,
+) -> Result<String> {
+    let index_bytes = tokio::fs::read(dir.join("index.json"))
+        .await
+        .map_err(|e| error::wrap("Failed to read index.json", e))?;
+    let index: OciImageIndex = serde_json::from_slice(&index_bytes)
+        .map_err(|e| Error::from_reason(format!("Failed to parse index.json: {}", e)))?;
+
+    let root = index
+        .manifests
+        .first()
+        .ok_or_else(|| Error::from_reason("index.json has no manifests".to_string()))?;
+
+    let bytes = tokio::fs::read(blob_path(dir, &root.digest))
+        .await
+        .map_err(|e| error::wrap(&format!("Failed to read blob {}", root.digest), e))?;
+
+    push_tree(client, http, protocol, reference, auth, auth_header, token_cache, dir, &bytes).await?;
+
+    let media_type = root
+        .media_type
+        .parse()
+        .map_err(|e| Error::from_reason(format!("index.json has an invalid media type: {}", e)))?;
+    client
+        .push_manifest_raw(reference, bytes.clone(), media_type)
+        .await
+        .map_err(|e| error::wrap_oci("Failed to push manifest", e))?;
+
+    Ok(sha256_hex(&bytes))
+}
+
+fn push_tree<'a>(
+    client: &'a Client,
+    http: &'a reqwest::Client,
+    protocol: &'a oci_client::client::ClientProtocol,
+    reference: &'a Reference,
+    auth: &'a RegistryAuth,
+    auth_header: Option<&'a str>,
+    token_cache: &'a auth::TokenCache,
+    dir: &'a Path,
+    bytes: &'a [u8],
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+    Box::pin(async move {
+        if let Ok(index) = serde_json::from_slice::<OciImageIndex>(bytes) {
+            if !index.manifests.is_empty() {
+                for entry in &index.manifests {
+                    let child_bytes = tokio::fs::read(blob_path(dir, &entry.digest))
+                        .await
+                        .map_err(|e| error::wrap(&format!("Failed to read blob {}", entry.digest), e))?;
+                    push_tree(client, http, protocol, reference, auth, auth_header, token_cache, dir, &child_bytes).await?;
+                    let child_media_type = entry
+                        .media_type
+                        .parse()
+                        .map_err(|e| Error::from_reason(format!("index.json has an invalid media type: {}", e)))?;
+                    client
+                        .push_manifest_raw(reference, child_bytes, child_media_type)
+                        .await
+                        .map_err(|e| error::wrap_oci("Failed to push referenced manifest", e))?;
+                }
+                return Ok(());
+            }
+        }
+
+        let manifest: OciImageManifest = serde_json::from_slice(bytes)
+            .map_err(|e| Error::from_reason(format!("Failed to parse manifest: {}", e)))?;
+
+        let mut blobs = vec![manifest.config.digest.clone()];
+        blobs.extend(manifest.layers.iter().map(|l| l.digest.clone()));
+
+        for digest in blobs {
+            let data = tokio::fs::read(blob_path(dir, &digest))
+                .await
+                .map_err(|e| error::wrap(&format!("Failed to read blob {}", digest), e))?;
+            let computed = sha256_hex(&data);
+            if computed != digest {
+                return Err(Error::from_reason(format!(
+                    "Blob digest mismatch in layout: expected {}, got {}",
+                    digest, computed
+                )));
+            }
+            let progress = crate::upload::ProgressSink::standalone(None);
+            let result = crate::upload::push_blob_chunked(http, protocol, reference, &data, &digest, auth_header, &progress).await;
+            if matches!(result.as_ref().err().and_then(error::status_of), Some(401)) {
+                let retry_header = refresh_auth_header(http, protocol, reference, auth, token_cache).await;
+                crate::upload::push_blob_chunked(http, protocol, reference, &data, &digest, retry_header.as_deref(), &progress).await?;
+            } else {
+                result?;
+            }
+        }
+
+        Ok(())
+    })
+}
+
@@ -1,38 +1,69 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use oci_client::{Client, Reference, secrets::RegistryAuth};
-use oci_client::client::{ClientConfig, ClientProtocol, ImageData, ImageLayer, Config, PushResponse};
+use oci_client::client::{ClientConfig, ClientProtocol, ImageData, ImageLayer, Config};
 use oci_client::manifest::{OciManifest, OciImageIndex, OciImageManifest};
 use serde::{Serialize, Deserialize};
 
 use std::str::FromStr;
 use std::collections::BTreeMap;
-
-// ===== Pure OCI Client Bindings for oci-client 0.14 =====
-// This file contains ONLY thin wrappers around oci-client 0.14 methods.
-// All high-level logic (multi-platform, ORAS-style operations, etc.) 
-// should be implemented in JavaScript/TypeScript.
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use futures::stream::{self, StreamExt};
+use tokio::sync::Semaphore;
+
+mod auth;
+mod digest;
+mod download;
+mod error;
+mod layout;
+mod platform;
+mod upload;
+use platform::{NapiIndexEntry, NapiPlatform};
+use upload::ProgressCallback;
+
+// ===== OCI Client Bindings for oci-client 0.14 =====
+// Most methods here are thin wrappers around oci-client 0.14 methods, plus
+// higher-level logic (chunked/concurrent upload, digest verification,
+// bearer-token auth, OCI image layout, multi-platform index handling) that
+// requires digest/size bookkeeping or raw HTTP access not worth pushing to
+// JavaScript/TypeScript callers.
 
 #[napi]
 pub struct OrasClient {
   inner: Client,
+  protocol: ClientProtocol,
+  http: reqwest::Client,
+  max_concurrent_upload: u32,
+  token_cache: auth::TokenCache,
 }
 
 #[napi(object)]
+#[derive(Clone)]
 pub struct AuthOptions {
   pub username: Option<String>,
   pub password: Option<String>,
-  // Note: oci-client 0.14 only supports Basic and Anonymous auth
-  // token and use_docker_config are kept for API compatibility but ignored
+  /// Bearer token to use directly, bypassing the WWW-Authenticate challenge flow.
   pub token: Option<String>,
+  /// When true, resolve credentials from `~/.docker/config.json` instead of
+  /// `username`/`password`.
   pub use_docker_config: Option<bool>,
 }
 
-// Helper to convert auth options to RegistryAuth
-// Note: oci-client 0.14 only supports Basic and Anonymous auth
-fn get_auth(auth: Option<AuthOptions>) -> Result<RegistryAuth> {
+// Helper to convert auth options to RegistryAuth.
+// Note: oci-client 0.14's RegistryAuth only has Basic and Anonymous variants;
+// `token`-based auth is resolved separately (see `resolve_raw_auth_header`)
+// for the raw HTTP paths that need to send an `Authorization` header
+// directly (chunked upload, streaming pull).
+fn get_auth(auth: Option<AuthOptions>, registry: &str) -> Result<RegistryAuth> {
     match auth {
         Some(opts) => {
+            if opts.use_docker_config.unwrap_or(false) {
+                if let Some((u, p)) = auth::docker_config_credentials(registry) {
+                    return Ok(RegistryAuth::Basic(u, p));
+                }
+                return Ok(RegistryAuth::Anonymous);
+            }
             if let (Some(u), Some(p)) = (opts.username, opts.password) {
                 Ok(RegistryAuth::Basic(u, p))
             } else {
@@ -91,22 +122,83 @@ pub struct NapiPushResponse {
 
 #[napi]
 impl OrasClient {
-  /// Create a new OCI client
+  /// Create a new OCI client.
+  /// max_concurrent_upload: Maximum number of layer blobs uploaded in parallel by `push`
+  /// (defaults to 3 when omitted).
   #[napi(constructor)]
-  pub fn new(insecure: Option<bool>) -> Self {
+  pub fn new(insecure: Option<bool>, max_concurrent_upload: Option<u32>) -> Self {
+    let protocol = if insecure.unwrap_or(false) {
+      ClientProtocol::Http
+    } else {
+      ClientProtocol::Https
+    };
     let config = ClientConfig {
-      protocol: if insecure.unwrap_or(false) {
-        ClientProtocol::Http
-      } else {
-        ClientProtocol::Https
-      },
+      protocol: protocol.clone(),
       ..Default::default()
     };
     Self {
       inner: Client::new(config),
+      protocol,
+      http: reqwest::Client::new(),
+      max_concurrent_upload: max_concurrent_upload.unwrap_or(3).max(1),
+      token_cache: auth::new_token_cache(),
     }
   }
 
+  /// Resolves the `Authorization` header for the raw HTTP paths (chunked
+  /// upload, streaming pull) that bypass oci-client's own request handling:
+  /// an explicit `token` wins, then a cached bearer token for this
+  /// registry+scope, then a bearer token obtained via the WWW-Authenticate
+  /// challenge flow, then plain Basic auth, then no auth at all.
+  async fn resolve_raw_auth_header(
+    &self,
+    reference: &Reference,
+    auth: &Option<AuthOptions>,
+  ) -> Option<String> {
+    let registry = reference.resolve_registry();
+    let scope = Self::raw_auth_scope(reference);
+
+    let (basic, token) = match auth {
+        Some(opts) => {
+            let basic = if opts.use_docker_config.unwrap_or(false) {
+                auth::docker_config_credentials(registry)
+            } else if let (Some(u), Some(p)) = (&opts.username, &opts.password) {
+                Some((u.clone(), p.clone()))
+            } else {
+                None
+            };
+            (basic, opts.token.clone())
+        }
+        None => (None, None),
+    };
+
+    let scheme = match self.protocol {
+        ClientProtocol::Https => "https",
+        ClientProtocol::Http => "http",
+        ClientProtocol::HttpsExcept(_) => "https",
+    };
+    let ping_url = format!("{}://{}/v2/", scheme, registry);
+
+    auth::resolve_header(&self.http, &ping_url, registry, &scope, basic, token, &self.token_cache).await
+  }
+
+  fn raw_auth_scope(reference: &Reference) -> String {
+    format!("repository:{}:pull,push", reference.repository())
+  }
+
+  /// Evicts the cached bearer token for `reference` and resolves a fresh
+  /// `Authorization` header, for use when a raw HTTP request made with the
+  /// previously cached token comes back `401 Unauthorized` (e.g. the token
+  /// expired mid-session).
+  async fn refresh_raw_auth_header(
+    &self,
+    reference: &Reference,
+    auth: &Option<AuthOptions>,
+  ) -> Option<String> {
+    auth::evict(&self.token_cache, reference.resolve_registry(), &Self::raw_auth_scope(reference)).await;
+    self.resolve_raw_auth_header(reference, auth).await
+  }
+
   /// Pull an image manifest from the registry.
   /// Returns: JSON string of the manifest (OciManifest enum, can be Image or ImageIndex)
   #[napi]
@@ -117,17 +209,19 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let (manifest, _digest) = self.inner.pull_manifest(&reference, &auth)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull manifest: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to pull manifest", e))?;
         
     serde_json::to_string_pretty(&manifest)
         .map_err(|e| Error::from_reason(format!("Failed to serialize manifest: {}", e)))
   }
 
   /// Pull a blob from the registry by digest.
+  /// verify: When true, recompute the sha256 of the received bytes and error if it
+  /// doesn't match `digest`.
   /// Returns: Buffer containing the blob data
   #[napi]
   pub async fn pull_blob(
@@ -135,25 +229,43 @@ impl OrasClient {
     image_ref: String,
     digest: String,
     auth: Option<AuthOptions>,
+    verify: Option<bool>,
   ) -> Result<Buffer> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
-    
+    let auth = get_auth(auth, reference.resolve_registry())?;
+
     // Store auth before pulling
     self.inner.store_auth_if_needed(reference.resolve_registry(), &auth).await;
-    
+
     let mut content = Vec::new();
-    
+
     self.inner.pull_blob(&reference, digest.as_str(), &mut content)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull blob: {}", e)))?;
-        
+        .map_err(|e| error::wrap_oci("Failed to pull blob", e))?;
+
+    if verify.unwrap_or(false) {
+        let actual = digest::sha256_hex(&content);
+        if actual != digest {
+            return Err(Error::from_reason(format!(
+                "Blob digest mismatch: expected {}, got {}",
+                digest, actual
+            )));
+        }
+    }
+
     Ok(Buffer::from(content))
   }
 
+  /// Computes the `sha256:<hex>` digest of `data`, in the form OCI registries
+  /// use to address blobs and manifests.
+  #[napi]
+  pub fn compute_digest(data: Buffer) -> String {
+    digest::sha256_hex(data.as_ref())
+  }
+
   /// Push a blob to the registry.
-  /// The digest must be pre-calculated and provided by the caller.
+  /// digest: sha256 digest of `data`. When omitted, it is computed natively.
   /// Returns: The digest of the pushed blob
   /// Note: oci-client 0.14 push_blob does NOT take auth parameter, but we accept it for API consistency
   #[napi]
@@ -161,25 +273,73 @@ impl OrasClient {
     &self,
     image_ref: String,
     data: Buffer,
-    digest: String,
+    digest: Option<String>,
     auth: Option<AuthOptions>,
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth_obj = get_auth(auth)?;
-    
+    let auth_obj = get_auth(auth, reference.resolve_registry())?;
+
     // Store auth before pushing
     self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
-    
+
     let content: Vec<u8> = data.to_vec();
-    
+    let digest = digest.unwrap_or_else(|| digest::sha256_hex(&content));
+
     self.inner.push_blob(&reference, &content, &digest)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to push blob: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to push blob", e))?;
 
     Ok(digest)
   }
 
+  /// Push a blob using the chunked upload protocol (open session, PATCH
+  /// sequential byte ranges, PUT to close), automatically falling back to a
+  /// monolithic single-PUT upload if the registry rejects chunked uploads.
+  /// progress: Optional callback invoked with cumulative bytes sent for this blob.
+  /// Returns: The digest of the pushed blob
+  #[napi]
+  pub async fn push_blob_chunked(
+    &self,
+    image_ref: String,
+    data: Buffer,
+    digest: String,
+    auth: Option<AuthOptions>,
+    progress: Option<ProgressCallback>,
+  ) -> Result<String> {
+    let reference = Reference::from_str(&image_ref)
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let auth_header = self.resolve_raw_auth_header(&reference, &auth).await;
+    let auth_obj = get_auth(auth.clone(), reference.resolve_registry())?;
+
+    self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
+
+    let progress = upload::ProgressSink::standalone(progress);
+    let result = upload::push_blob_chunked(
+        &self.http,
+        &self.protocol,
+        &reference,
+        data.as_ref(),
+        &digest,
+        auth_header.as_deref(),
+        &progress,
+    ).await;
+
+    if matches!(result.as_ref().err().and_then(error::status_of), Some(401)) {
+        let retry_header = self.refresh_raw_auth_header(&reference, &auth).await;
+        return upload::push_blob_chunked(
+            &self.http,
+            &self.protocol,
+            &reference,
+            data.as_ref(),
+            &digest,
+            retry_header.as_deref(),
+            &progress,
+        ).await;
+    }
+    result
+  }
+
   /// Push a manifest to the registry.
   /// manifest_json: JSON string of the OCI manifest (OciManifest enum)
   /// Returns: The manifest URL
@@ -193,7 +353,7 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth_obj = get_auth(auth)?;
+    let auth_obj = get_auth(auth, reference.resolve_registry())?;
     
     // Store auth before pushing
     self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
@@ -203,7 +363,7 @@ impl OrasClient {
     
     self.inner.push_manifest(&reference, &manifest)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to push manifest: {}", e)))
+        .map_err(|e| error::wrap_oci("Failed to push manifest", e))
   }
 
   /// List tags for an image repository.
@@ -220,7 +380,7 @@ impl OrasClient {
   ) -> Result<Vec<String>> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let tags = self.inner.list_tags(
         &reference,
@@ -229,7 +389,7 @@ impl OrasClient {
         last.as_deref()
     )
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to list tags: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to list tags", e))?;
     
     Ok(tags.tags)
   }
@@ -246,7 +406,7 @@ impl OrasClient {
   ) -> Result<Buffer> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let media_types: Vec<&str> = accepted_media_types
         .as_ref()
@@ -259,7 +419,7 @@ impl OrasClient {
         &media_types
     )
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull raw manifest: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to pull raw manifest", e))?;
     
     Ok(Buffer::from(manifest_bytes))
   }
@@ -279,7 +439,7 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth_obj = get_auth(auth)?;
+    let auth_obj = get_auth(auth, reference.resolve_registry())?;
     
     // Store auth before pushing
     self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
@@ -288,7 +448,7 @@ impl OrasClient {
     
     self.inner.push_manifest_raw(&reference, bytes, content_type.parse().unwrap())
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to push raw manifest: {}", e)))
+        .map_err(|e| error::wrap_oci("Failed to push raw manifest", e))
   }
 
   /// Fetch the manifest digest without pulling the full manifest.
@@ -301,11 +461,11 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     self.inner.fetch_manifest_digest(&reference, &auth)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to fetch manifest digest: {}", e)))
+        .map_err(|e| error::wrap_oci("Failed to fetch manifest digest", e))
   }
 
   /// Pull manifest and config together.
@@ -318,11 +478,11 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let (manifest, digest, config) = self.inner.pull_manifest_and_config(&reference, &auth)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull manifest and config: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to pull manifest and config", e))?;
     
     let result = serde_json::json!({
         "manifest": manifest,
@@ -347,14 +507,14 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth_obj = get_auth(auth)?;
+    let auth_obj = get_auth(auth, reference.resolve_registry())?;
     
     // Store auth before pulling
     self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
     
     let referrers = self.inner.pull_referrers(&reference, artifact_type.as_deref())
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull referrers: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to pull referrers", e))?;
     
     serde_json::to_string_pretty(&referrers)
         .map_err(|e| Error::from_reason(format!("Failed to serialize referrers: {}", e)))
@@ -377,14 +537,14 @@ impl OrasClient {
         .map_err(|e| Error::from_reason(e.to_string()))?;
     let from_reference = Reference::from_str(&from_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth_obj = get_auth(auth)?;
+    let auth_obj = get_auth(auth, target_reference.resolve_registry())?;
     
     // Store auth before mounting
     self.inner.store_auth_if_needed(target_reference.resolve_registry(), &auth_obj).await;
     
     self.inner.mount_blob(&target_reference, &from_reference, &digest)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to mount blob: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to mount blob", e))?;
     
     Ok(format!("Mounted blob {} from {} to {}", digest, from_ref, target_ref))
   }
@@ -402,14 +562,14 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let manifest_list: OciImageIndex = serde_json::from_str(&manifest_list_json)
         .map_err(|e| Error::from_reason(format!("Failed to parse manifest list JSON: {}", e)))?;
     
     self.inner.push_manifest_list(&reference, &auth, manifest_list)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to push manifest list: {}", e)))
+        .map_err(|e| error::wrap_oci("Failed to push manifest list", e))
   }
 
   /// Pull an image manifest, automatically resolving platform if it's an Image Index.
@@ -422,11 +582,11 @@ impl OrasClient {
   ) -> Result<String> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let (manifest, _digest) = self.inner.pull_image_manifest(&reference, &auth)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull image manifest: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to pull image manifest", e))?;
         
     serde_json::to_string_pretty(&manifest)
         .map_err(|e| Error::from_reason(format!("Failed to serialize manifest: {}", e)))
@@ -444,7 +604,7 @@ impl OrasClient {
   ) -> Result<NapiImageData> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
     
     let media_types_owned = accepted_media_types.unwrap_or_default();
     let media_types_vec: Vec<&str> = media_types_owned
@@ -454,7 +614,7 @@ impl OrasClient {
 
     let image_data: ImageData = self.inner.pull(&reference, &auth, media_types_vec)
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to pull image: {}", e)))?;
+        .map_err(|e| error::wrap_oci("Failed to pull image", e))?;
     
     let napi_layers: Vec<NapiImageLayer> = image_data.layers.into_iter().map(|layer| {
         NapiImageLayer {
@@ -478,9 +638,16 @@ impl OrasClient {
   }
 
   /// Push an image (layers, config, and optional manifest).
+  /// Layer and config blobs are uploaded concurrently (bounded by
+  /// `max_concurrent_upload`, see `OrasClient::new`) via the chunked uploader;
+  /// only the manifest itself is then pushed via oci-client, so no blob is
+  /// ever sent to the registry a second time.
   /// layers_json: JSON string of Vec<NapiImageLayer>
   /// config_json: JSON string of NapiConfig
   /// manifest_json: Optional JSON string of OciImageManifest
+  /// progress: Optional callback invoked with the cumulative bytes sent so far,
+  /// summed across all concurrently uploading layers via a shared counter
+  /// (not just the most recently reported layer's own total).
   /// Returns: NapiPushResponse
   #[napi]
   pub async fn push(
@@ -490,14 +657,19 @@ impl OrasClient {
     config_json: String,
     auth: Option<AuthOptions>,
     manifest_json: Option<String>,
+    progress: Option<ProgressCallback>,
   ) -> Result<NapiPushResponse> {
     let reference = Reference::from_str(&image_ref)
         .map_err(|e| Error::from_reason(e.to_string()))?;
-    let auth = get_auth(auth)?;
+    let auth_header = self.resolve_raw_auth_header(&reference, &auth).await;
+    let auth_opts = auth.clone();
+    let auth = get_auth(auth, reference.resolve_registry())?;
+
+    self.inner.store_auth_if_needed(reference.resolve_registry(), &auth).await;
 
     let json_layers: Vec<JsonImageLayer> = serde_json::from_str(&layers_json)
         .map_err(|e| Error::from_reason(format!("Failed to parse layers JSON: {}", e)))?;
-    
+
     let layers: Vec<ImageLayer> = json_layers.into_iter().map(|json_layer| {
         ImageLayer {
             data: json_layer.data,
@@ -508,27 +680,235 @@ impl OrasClient {
 
     let json_config: JsonConfig = serde_json::from_str(&config_json)
         .map_err(|e| Error::from_reason(format!("Failed to parse config JSON: {}", e)))?;
-    
+
     let config = Config {
         data: json_config.data,
         media_type: json_config.media_type,
         annotations: json_config.annotations,
     };
 
-    let manifest: Option<OciImageManifest> = if let Some(m_json) = manifest_json {
-        Some(serde_json::from_str(&m_json)
-            .map_err(|e| Error::from_reason(format!("Failed to parse manifest JSON: {}", e)))?)
+    let mut blobs: Vec<Vec<u8>> = layers.iter().map(|l| l.data.clone()).collect();
+    blobs.push(config.data.clone());
+
+    let semaphore = Arc::new(Semaphore::new(self.max_concurrent_upload as usize));
+    let shared_sent = Arc::new(AtomicU64::new(0));
+    let client = self;
+    let upload_results: Vec<Result<String>> = stream::iter(blobs.into_iter())
+        .map(|data| {
+            let semaphore = semaphore.clone();
+            let reference = reference.clone();
+            let http = client.http.clone();
+            let protocol = client.protocol.clone();
+            let auth_header = auth_header.clone();
+            let auth_opts = auth_opts.clone();
+            let progress = upload::ProgressSink::shared(progress.clone(), shared_sent.clone());
+            async move {
+                let _permit = semaphore.acquire_owned().await.unwrap();
+                let digest = digest::sha256_hex(&data);
+                let result = upload::push_blob_chunked(&http, &protocol, &reference, &data, &digest, auth_header.as_deref(), &progress).await;
+                if matches!(result.as_ref().err().and_then(error::status_of), Some(401)) {
+                    let retry_header = client.refresh_raw_auth_header(&reference, &auth_opts).await;
+                    return upload::push_blob_chunked(&http, &protocol, &reference, &data, &digest, retry_header.as_deref(), &progress).await;
+                }
+                result
+            }
+        })
+        .buffer_unordered(self.max_concurrent_upload as usize)
+        .collect()
+        .await;
+
+    for result in upload_results {
+        result?;
+    }
+
+    let manifest: OciImageManifest = if let Some(m_json) = manifest_json {
+        serde_json::from_str(&m_json)
+            .map_err(|e| Error::from_reason(format!("Failed to parse manifest JSON: {}", e)))?
     } else {
-        None
+        OciImageManifest::build(&layers, &config, None)
     };
 
-    let push_response: PushResponse = self.inner.push(&reference, &layers, config, &auth, manifest)
+    let config_digest = digest::sha256_hex(&config.data);
+    let config_url = format!("{}/blobs/{}", upload::blob_base_url(&self.protocol, &reference), config_digest);
+
+    let manifest_url = self.inner.push_manifest(&reference, &OciManifest::Image(manifest))
         .await
-        .map_err(|e| Error::from_reason(format!("Failed to push image: {}", e)))?;
-    
+        .map_err(|e| error::wrap_oci("Failed to push manifest", e))?;
+
     Ok(NapiPushResponse {
-        config_url: push_response.config_url,
-        manifest_url: push_response.manifest_url,
+        config_url,
+        manifest_url,
     })
   }
+
+  /// Pulls `image_ref` and writes it to `dir` as a standard OCI Image Layout
+  /// (`oci-layout` marker, `index.json`, and `blobs/sha256/<hex>` files),
+  /// so it can be staged offline and moved between registries.
+  #[napi]
+  pub async fn export_to_layout(
+    &self,
+    image_ref: String,
+    dir: String,
+    auth: Option<AuthOptions>,
+  ) -> Result<()> {
+    let reference = Reference::from_str(&image_ref)
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
+
+    layout::export_to_layout(&self.inner, &reference, &auth, std::path::Path::new(&dir)).await
+  }
+
+  /// Reads a standard OCI Image Layout from `dir` and pushes it (every
+  /// referenced blob, then the manifest/index) to `image_ref`.
+  /// Returns: The digest of the pushed top-level manifest/index
+  #[napi]
+  pub async fn push_from_layout(
+    &self,
+    dir: String,
+    image_ref: String,
+    auth: Option<AuthOptions>,
+  ) -> Result<String> {
+    let reference = Reference::from_str(&image_ref)
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let auth_header = self.resolve_raw_auth_header(&reference, &auth).await;
+    let auth_obj = get_auth(auth, reference.resolve_registry())?;
+
+    self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
+
+    layout::push_from_layout(
+        &self.inner,
+        &self.http,
+        &self.protocol,
+        &reference,
+        &auth_obj,
+        auth_header.as_deref(),
+        &self.token_cache,
+        std::path::Path::new(&dir),
+    ).await
+  }
+
+  /// Builds an OCI Image Index from per-platform manifests, computing each
+  /// manifest's sha256 digest and byte size natively, and pushes it.
+  /// entries: One entry per platform, each `{ manifest_json, platform }`
+  /// Returns: The digest of the pushed index
+  #[napi]
+  pub async fn build_and_push_index(
+    &self,
+    image_ref: String,
+    entries: Vec<NapiIndexEntry>,
+    auth: Option<AuthOptions>,
+  ) -> Result<String> {
+    let reference = Reference::from_str(&image_ref)
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
+
+    let index = platform::build_index(&entries)?;
+    let digest = digest::sha256_hex(serde_json::to_string(&index)
+        .map_err(|e| Error::from_reason(format!("Failed to serialize index: {}", e)))?
+        .as_bytes());
+
+    self.inner.push_manifest_list(&reference, &auth, index)
+        .await
+        .map_err(|e| error::wrap_oci("Failed to push image index", e))?;
+
+    Ok(digest)
+  }
+
+  /// Pulls the image index at `image_ref` and resolves it to the manifest
+  /// for `platform`, falling back to `default_platform` when no exact
+  /// architecture+os+variant match exists.
+  /// Returns: JSON string of the resolved OciImageManifest
+  #[napi]
+  pub async fn pull_for_platform(
+    &self,
+    image_ref: String,
+    platform: NapiPlatform,
+    default_platform: Option<NapiPlatform>,
+    auth: Option<AuthOptions>,
+  ) -> Result<String> {
+    let reference = Reference::from_str(&image_ref)
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let auth = get_auth(auth, reference.resolve_registry())?;
+
+    let (manifest, _digest) = self.inner.pull_manifest(&reference, &auth)
+        .await
+        .map_err(|e| error::wrap_oci("Failed to pull manifest", e))?;
+
+    let index = match manifest {
+        OciManifest::ImageIndex(index) => index,
+        OciManifest::Image(image_manifest) => {
+            return serde_json::to_string_pretty(&image_manifest)
+                .map_err(|e| Error::from_reason(format!("Failed to serialize manifest: {}", e)));
+        }
+    };
+
+    let descriptor = platform::select_descriptor(&index, &platform, &default_platform)
+        .ok_or_else(|| error::wrap(
+            "No matching platform in image index",
+            format!("{}/{} (variant {:?})", platform.os, platform.architecture, platform.variant),
+        ))?;
+
+    let child_reference = Reference::from_str(&format!(
+        "{}/{}@{}",
+        reference.resolve_registry(),
+        reference.repository(),
+        descriptor.digest
+    )).map_err(|e| Error::from_reason(e.to_string()))?;
+
+    let (resolved, _digest) = self.inner.pull_image_manifest(&child_reference, &auth)
+        .await
+        .map_err(|e| error::wrap_oci("Failed to pull platform-specific manifest", e))?;
+
+    serde_json::to_string_pretty(&resolved)
+        .map_err(|e| Error::from_reason(format!("Failed to serialize manifest: {}", e)))
+  }
+
+  /// Streams a blob directly to `dest_path` instead of buffering it in
+  /// memory, so multi-GB layers don't exhaust the Node heap.
+  /// verify: When true, recompute the sha256 of the streamed bytes and error if it
+  /// doesn't match `digest`.
+  /// progress: Optional callback invoked with cumulative bytes written.
+  #[napi]
+  pub async fn pull_blob_to_file(
+    &self,
+    image_ref: String,
+    digest: String,
+    dest_path: String,
+    auth: Option<AuthOptions>,
+    progress: Option<ProgressCallback>,
+    verify: Option<bool>,
+  ) -> Result<()> {
+    let reference = Reference::from_str(&image_ref)
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    let auth_header = self.resolve_raw_auth_header(&reference, &auth).await;
+    let auth_obj = get_auth(auth.clone(), reference.resolve_registry())?;
+
+    self.inner.store_auth_if_needed(reference.resolve_registry(), &auth_obj).await;
+
+    let result = download::pull_blob_to_file(
+        &self.http,
+        &self.protocol,
+        &reference,
+        &digest,
+        &dest_path,
+        auth_header.as_deref(),
+        progress.clone(),
+        verify.unwrap_or(false),
+    ).await;
+
+    if matches!(result.as_ref().err().and_then(error::status_of), Some(401)) {
+        let retry_header = self.refresh_raw_auth_header(&reference, &auth).await;
+        return download::pull_blob_to_file(
+            &self.http,
+            &self.protocol,
+            &reference,
+            &digest,
+            &dest_path,
+            retry_header.as_deref(),
+            progress,
+            verify.unwrap_or(false),
+        ).await;
+    }
+    result
+  }
 }
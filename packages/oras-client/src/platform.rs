@@ -0,0 +1,192 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+use oci_client::manifest::{OciDescriptor, OciImageIndex, OciPlatform};
+
+use crate::digest::sha256_hex;
+
+const MEDIA_TYPE_IMAGE_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+const MEDIA_TYPE_IMAGE_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+
+#[napi(object)]
+pub struct NapiPlatform {
+    pub architecture: String,
+    pub os: String,
+    pub variant: Option<String>,
+}
+
+impl From<&NapiPlatform> for OciPlatform {
+    fn from(p: &NapiPlatform) -> Self {
+        OciPlatform {
+            architecture: p.architecture.clone(),
+            os: p.os.clone(),
+            os_version: None,
+            os_features: None,
+            variant: p.variant.clone(),
+            features: None,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct NapiIndexEntry {
+    pub manifest_json: String,
+    pub platform: NapiPlatform,
+}
+
+/// Assembles an `OciImageIndex` from `entries`, computing each manifest's
+/// sha256 digest and byte size natively (this digest/size bookkeeping is
+/// exactly what's error-prone to do by hand in JS).
+pub fn build_index(entries: &[NapiIndexEntry]) -> Result<OciImageIndex> {
+    let manifests = entries
+        .iter()
+        .map(|entry| {
+            let bytes = entry.manifest_json.as_bytes();
+            Ok(OciDescriptor {
+                media_type: MEDIA_TYPE_IMAGE_MANIFEST.to_string(),
+                digest: sha256_hex(bytes),
+                size: bytes.len() as i64,
+                urls: None,
+                annotations: None,
+                platform: Some(OciPlatform::from(&entry.platform)),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(OciImageIndex {
+        schema_version: 2,
+        media_type: Some(MEDIA_TYPE_IMAGE_INDEX.to_string()),
+        manifests,
+        annotations: None,
+    })
+}
+
+/// Selects the descriptor in `index` matching `platform` exactly
+/// (architecture + os + variant), then `default_platform` exactly, then
+/// `platform` on architecture + os only, then `default_platform` on
+/// architecture + os only. Exact matches against both the requested
+/// platform and the caller-specified default are tried before either one's
+/// loose match, so a loose match on the requested platform (e.g. a
+/// different variant) never shadows an exact match on the default.
+pub fn select_descriptor<'a>(
+    index: &'a OciImageIndex,
+    platform: &NapiPlatform,
+    default_platform: &Option<NapiPlatform>,
+) -> Option<&'a OciDescriptor> {
+    find_exact(index, platform)
+        .or_else(|| default_platform.as_ref().and_then(|p| find_exact(index, p)))
+        .or_else(|| find_loose(index, platform))
+        .or_else(|| default_platform.as_ref().and_then(|p| find_loose(index, p)))
+}
+
+fn find_exact<'a>(index: &'a OciImageIndex, platform: &NapiPlatform) -> Option<&'a OciDescriptor> {
+    index.manifests.iter().find(|d| matches_exact(d, platform))
+}
+
+fn find_loose<'a>(index: &'a OciImageIndex, platform: &NapiPlatform) -> Option<&'a OciDescriptor> {
+    index.manifests.iter().find(|d| matches_loose(d, platform))
+}
+
+fn matches_exact(descriptor: &OciDescriptor, platform: &NapiPlatform) -> bool {
+    match &descriptor.platform {
+        Some(p) => {
+            p.architecture == platform.architecture
+                && p.os == platform.os
+                && p.variant == platform.variant
+        }
+        None => false,
+    }
+}
+
+fn matches_loose(descriptor: &OciDescriptor, platform: &NapiPlatform) -> bool {
+    match &descriptor.platform {
+        Some(p) => p.architecture == platform.architecture && p.os == platform.os,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn platform(architecture: &str, os: &str, variant: Option<&str>) -> NapiPlatform {
+        NapiPlatform {
+            architecture: architecture.to_string(),
+            os: os.to_string(),
+            variant: variant.map(|v| v.to_string()),
+        }
+    }
+
+    fn descriptor(architecture: &str, os: &str, variant: Option<&str>) -> OciDescriptor {
+        OciDescriptor {
+            media_type: MEDIA_TYPE_IMAGE_MANIFEST.to_string(),
+            digest: format!("sha256:{}-{}-{:?}", architecture, os, variant),
+            size: 0,
+            urls: None,
+            annotations: None,
+            platform: Some(OciPlatform {
+                architecture: architecture.to_string(),
+                os: os.to_string(),
+                os_version: None,
+                os_features: None,
+                variant: variant.map(|v| v.to_string()),
+                features: None,
+            }),
+        }
+    }
+
+    fn index(descriptors: Vec<OciDescriptor>) -> OciImageIndex {
+        OciImageIndex {
+            schema_version: 2,
+            media_type: Some(MEDIA_TYPE_IMAGE_INDEX.to_string()),
+            manifests: descriptors,
+            annotations: None,
+        }
+    }
+
+    #[test]
+    fn exact_match_wins_over_default() {
+        let idx = index(vec![
+            descriptor("arm", "linux", Some("v7")),
+            descriptor("amd64", "linux", None),
+        ]);
+        let requested = platform("arm", "linux", Some("v7"));
+        let default = Some(platform("amd64", "linux", None));
+
+        let found = select_descriptor(&idx, &requested, &default).unwrap();
+        assert_eq!(found.platform.as_ref().unwrap().variant.as_deref(), Some("v7"));
+    }
+
+    #[test]
+    fn default_exact_match_wins_over_requested_loose_match() {
+        // Index has a different variant of the requested platform (arm/linux/v6)
+        // as well as an exact match for the caller's explicit default
+        // (amd64/linux). The default's exact match must win: a loose match on
+        // the wrong variant of the requested platform must never shadow it.
+        let idx = index(vec![
+            descriptor("arm", "linux", Some("v6")),
+            descriptor("amd64", "linux", None),
+        ]);
+        let requested = platform("arm", "linux", Some("v7"));
+        let default = Some(platform("amd64", "linux", None));
+
+        let found = select_descriptor(&idx, &requested, &default).unwrap();
+        assert_eq!(found.platform.as_ref().unwrap().architecture, "amd64");
+    }
+
+    #[test]
+    fn loose_match_on_requested_platform_used_when_no_default() {
+        let idx = index(vec![descriptor("arm", "linux", Some("v6"))]);
+        let requested = platform("arm", "linux", Some("v7"));
+
+        let found = select_descriptor(&idx, &requested, &None).unwrap();
+        assert_eq!(found.platform.as_ref().unwrap().variant.as_deref(), Some("v6"));
+    }
+
+    #[test]
+    fn no_match_returns_none() {
+        let idx = index(vec![descriptor("arm", "linux", Some("v6"))]);
+        let requested = platform("amd64", "windows", None);
+
+        assert!(select_descriptor(&idx, &requested, &None).is_none());
+    }
+}
@@ -0,0 +1,253 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use oci_client::client::ClientProtocol;
+use oci_client::Reference;
+
+use crate::digest::sha256_hex;
+
+/// Size of each chunk sent during a chunked blob upload.
+/// 5 MiB keeps individual PATCH requests small while still amortizing
+/// per-request overhead on large layers.
+const CHUNK_SIZE: usize = 5 * 1024 * 1024;
+
+pub(crate) type ProgressCallback = ThreadsafeFunction<f64, ErrorStrategy::CalleeHandled>;
+
+/// Adapts a JS progress callback to either a single blob's own cumulative
+/// byte count (`standalone`), or a total shared across several concurrently
+/// uploaded blobs (`shared`) — used by `OrasClient::push` so one callback
+/// reports a running cross-layer sum instead of each layer's progress
+/// independently resetting to zero.
+pub(crate) struct ProgressSink {
+    callback: Option<ProgressCallback>,
+    shared_total: Option<Arc<AtomicU64>>,
+    last_reported: AtomicU64,
+}
+
+impl ProgressSink {
+    pub(crate) fn standalone(callback: Option<ProgressCallback>) -> Self {
+        Self {
+            callback,
+            shared_total: None,
+            last_reported: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn shared(callback: Option<ProgressCallback>, shared_total: Arc<AtomicU64>) -> Self {
+        Self {
+            callback,
+            shared_total: Some(shared_total),
+            last_reported: AtomicU64::new(0),
+        }
+    }
+
+    /// Reports that this blob's own cumulative bytes transferred so far is
+    /// `cumulative_local`; translates that into a delta so the `shared` sinks
+    /// of several blobs (one sink per blob) sum correctly into their common
+    /// total.
+    fn report(&self, cumulative_local: usize) {
+        let Some(cb) = &self.callback else { return };
+        let cumulative_local = cumulative_local as u64;
+        let previous = self.last_reported.swap(cumulative_local, Ordering::SeqCst);
+        let delta = cumulative_local.saturating_sub(previous);
+        let value = match &self.shared_total {
+            Some(total) => total.fetch_add(delta, Ordering::SeqCst) + delta,
+            None => cumulative_local,
+        };
+        cb.call(Ok(value as f64), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+}
+
+pub(crate) fn blob_base_url(protocol: &ClientProtocol, reference: &Reference) -> String {
+    let scheme = match protocol {
+        ClientProtocol::Https => "https",
+        ClientProtocol::Http => "http",
+        ClientProtocol::HttpsExcept(_) => "https",
+    };
+    format!(
+        "{}://{}/v2/{}",
+        scheme,
+        reference.resolve_registry(),
+        reference.repository()
+    )
+}
+
+/// Returns `true` when a registry response indicates it does not support
+/// chunked uploads and callers should retry with a monolithic PUT instead.
+fn is_chunked_unsupported(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::METHOD_NOT_ALLOWED || status == reqwest::StatusCode::BAD_REQUEST
+}
+
+/// Uploads `data` to `reference` as a single blob, using the chunked upload
+/// protocol (open session, PATCH sequential ranges, PUT to close) and
+/// automatically falling back to a monolithic PUT when the registry rejects
+/// chunked uploads outright.
+pub(crate) async fn push_blob_chunked(
+    http: &reqwest::Client,
+    protocol: &ClientProtocol,
+    reference: &Reference,
+    data: &[u8],
+    digest: &str,
+    auth_header: Option<&str>,
+    progress: &ProgressSink,
+) -> Result<String> {
+    let base = blob_base_url(protocol, reference);
+
+    let mut open_req = http.post(format!("{}/blobs/uploads/", base));
+    if let Some(auth) = auth_header {
+        open_req = open_req.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let open_resp = open_req
+        .send()
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to open blob upload session: {}", e)))?;
+
+    if is_chunked_unsupported(open_resp.status()) {
+        return push_blob_monolithic(http, &base, data, digest, auth_header, progress).await;
+    }
+    if !open_resp.status().is_success() {
+        let status = open_resp.status();
+        let body = open_resp.text().await.unwrap_or_default();
+        return Err(crate::error::from_response(status, &body, "Failed to open blob upload session"));
+    }
+
+    let mut location = open_resp
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| Error::from_reason("Registry did not return a Location header".to_string()))?
+        .to_string();
+
+    let mut sent = 0usize;
+    for chunk in data.chunks(CHUNK_SIZE) {
+        let start = sent;
+        let end = sent + chunk.len() - 1;
+        let url = absolute_location(&base, &location);
+
+        let mut req = http
+            .patch(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .header(reqwest::header::CONTENT_RANGE, format!("{}-{}", start, end))
+            .body(chunk.to_vec());
+        if let Some(auth) = auth_header {
+            req = req.header(reqwest::header::AUTHORIZATION, auth);
+        }
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| Error::from_reason(format!("Failed to PATCH blob chunk: {}", e)))?;
+
+        if is_chunked_unsupported(resp.status()) {
+            return push_blob_monolithic(http, &base, data, digest, auth_header, progress).await;
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(crate::error::from_response(status, &body, "Failed to PATCH blob chunk"));
+        }
+
+        location = resp
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+            .unwrap_or(location);
+
+        sent += chunk.len();
+        progress.report(sent);
+    }
+
+    let close_url = format!("{}{}digest={}", absolute_location(&base, &location), separator(&location), digest);
+    let mut close_req = http.put(&close_url);
+    if let Some(auth) = auth_header {
+        close_req = close_req.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let close_resp = close_req
+        .send()
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to close blob upload: {}", e)))?;
+
+    if !close_resp.status().is_success() {
+        let status = close_resp.status();
+        let body = close_resp.text().await.unwrap_or_default();
+        return Err(crate::error::from_response(status, &body, "Failed to close blob upload"));
+    }
+
+    Ok(digest.to_string())
+}
+
+async fn push_blob_monolithic(
+    http: &reqwest::Client,
+    base: &str,
+    data: &[u8],
+    digest: &str,
+    auth_header: Option<&str>,
+    progress: &ProgressSink,
+) -> Result<String> {
+    let mut open_req = http.post(format!("{}/blobs/uploads/", base));
+    if let Some(auth) = auth_header {
+        open_req = open_req.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let open_resp = open_req
+        .send()
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to open blob upload session: {}", e)))?;
+
+    if !open_resp.status().is_success() {
+        let status = open_resp.status();
+        let body = open_resp.text().await.unwrap_or_default();
+        return Err(crate::error::from_response(status, &body, "Failed to open blob upload session"));
+    }
+
+    let location = open_resp
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| Error::from_reason("Registry did not return a Location header".to_string()))?
+        .to_string();
+
+    let url = absolute_location(base, &location);
+    let put_url = format!("{}{}digest={}", url, separator(&location), digest);
+
+    let mut put_req = http
+        .put(&put_url)
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .body(data.to_vec());
+    if let Some(auth) = auth_header {
+        put_req = put_req.header(reqwest::header::AUTHORIZATION, auth);
+    }
+    let put_resp = put_req
+        .send()
+        .await
+        .map_err(|e| Error::from_reason(format!("Failed to PUT monolithic blob: {}", e)))?;
+
+    if !put_resp.status().is_success() {
+        let status = put_resp.status();
+        let body = put_resp.text().await.unwrap_or_default();
+        return Err(crate::error::from_response(status, &body, "Failed to PUT monolithic blob"));
+    }
+
+    progress.report(data.len());
+    Ok(digest.to_string())
+}
+
+fn separator(location: &str) -> &'static str {
+    if location.contains('?') {
+        "&"
+    } else {
+        "?"
+    }
+}
+
+fn absolute_location(base: &str, location: &str) -> String {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        location.to_string()
+    } else if let Some(registry_root) = base.split("/v2/").next() {
+        format!("{}{}", registry_root, location)
+    } else {
+        location.to_string()
+    }
+}